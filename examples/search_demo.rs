@@ -1,34 +1,59 @@
 use std::io::{self, Write};
+use std::path::Path;
 use iindex::iindex::InvertedIndex;
+use iindex::query::parse_query;
+use iindex::tokenizer::{PorterStemmer, SimpleTokenizer, StopWordFilter, TokenizerPipeline};
+
+const INDEX_PATH: &str = "search_demo_index.bin";
+
+fn build_tokenizer() -> TokenizerPipeline {
+    TokenizerPipeline::new(SimpleTokenizer)
+        .with_filter(StopWordFilter::english())
+        .with_filter(PorterStemmer)
+}
 
 fn main() {
     println!("=== Inverted Index Demo ===\n");
 
-    // Create and populate the index
-    let mut index = InvertedIndex::new();
-
-    // Read sample documents
-    let sample_docs = [
-        "The quick brown fox jumps over the lazy dog",
-        "A journey of a thousand miles begins with a single step",
-        "To be or not to be, that is the question",
-        "All that glitters is not gold",
-        "The early bird catches the worm"
-    ];
-
-    println!("Indexing {} documents...\n", sample_docs.len());
-    for (i, doc) in sample_docs.iter().enumerate() {
-        index.insert_document(doc);
-        println!("  Doc {}: {}", i, doc);
-    }
+    let index_path = Path::new(INDEX_PATH);
+    let mut index = if index_path.exists() {
+        println!("Loading existing index from {}...\n", INDEX_PATH);
+        InvertedIndex::open(index_path, build_tokenizer()).expect("failed to open saved index")
+    } else {
+        let mut index = InvertedIndex::new(build_tokenizer());
+
+        let sample_docs = [
+            "The quick brown fox jumps over the lazy dog",
+            "A journey of a thousand miles begins with a single step",
+            "To be or not to be, that is the question",
+            "All that glitters is not gold",
+            "The early bird catches the worm"
+        ];
+
+        println!("Indexing {} documents...\n", sample_docs.len());
+        for (i, doc) in sample_docs.iter().enumerate() {
+            index.insert_document(doc);
+            println!("  Doc {}: {}", i, doc);
+        }
+
+        index.add_synonym("hound", ["dog"]);
+        index.add_synonym("canine", ["dog"]);
+
+        index.save(index_path).expect("failed to save index");
+        index
+    };
 
     // Interactive search loop
     loop {
         println!("\n--- Search Menu ---");
         println!("1. Search (OR - any token matches)");
         println!("2. Search (AND - all tokens match)");
-        println!("3. Show document by ID");
-        println!("4. Exit");
+        println!("3. Search (boolean query, e.g. \"hello AND (world OR fox) NOT lazy\")");
+        println!("4. Search (fuzzy - tolerates typos)");
+        println!("5. Show document by ID");
+        println!("6. Add a document (saved immediately)");
+        println!("7. Add a synonym (saved immediately)");
+        println!("8. Exit");
         print!("\nChoose an option: ");
         io::stdout().flush().unwrap();
 
@@ -38,8 +63,12 @@ fn main() {
         match choice.trim() {
             "1" => search_or(&index),
             "2" => search_and(&index),
-            "3" => show_document(&index),
-            "4" => {
+            "3" => search_query(&index),
+            "4" => search_fuzzy(&index),
+            "5" => show_document(&index),
+            "6" => add_document(&mut index, index_path),
+            "7" => add_synonym(&mut index, index_path),
+            "8" => {
                 println!("Goodbye!");
                 break;
             }
@@ -96,6 +125,97 @@ fn search_and(index: &InvertedIndex) {
     }
 }
 
+fn search_query(index: &InvertedIndex) {
+    print!("Enter boolean query: ");
+    io::stdout().flush().unwrap();
+
+    let mut query = String::new();
+    io::stdin().read_line(&mut query).unwrap();
+    let query = query.trim();
+
+    let op = match parse_query(query) {
+        Ok(op) => op,
+        Err(err) => {
+            println!("Invalid query: {}", err);
+            return;
+        }
+    };
+    let results = index.search(&op);
+
+    if results.is_empty() {
+        println!("No documents found matching '{}'", query);
+    } else {
+        println!("\nFound {} documents matching '{}':", results.len(), query);
+        let mut sorted_results: Vec<_> = results.iter().collect();
+        sorted_results.sort();
+        for doc_id in sorted_results {
+            if let Some(doc) = index.get_document(*doc_id) {
+                println!("  [{}] {}", doc_id, doc);
+            }
+        }
+    }
+}
+
+fn search_fuzzy(index: &InvertedIndex) {
+    print!("Enter search query: ");
+    io::stdout().flush().unwrap();
+
+    let mut query = String::new();
+    io::stdin().read_line(&mut query).unwrap();
+    let query = query.trim();
+
+    let results = index.search_fuzzy(query, 2);
+
+    if results.is_empty() {
+        println!("No documents found matching '{}'", query);
+    } else {
+        println!("\nFound {} documents matching '{}' (fuzzy search):", results.len(), query);
+        let mut sorted_results: Vec<_> = results.iter().collect();
+        sorted_results.sort();
+        for doc_id in sorted_results {
+            if let Some(doc) = index.get_document(*doc_id) {
+                println!("  [{}] {}", doc_id, doc);
+            }
+        }
+    }
+}
+
+fn add_document(index: &mut InvertedIndex, index_path: &Path) {
+    print!("Enter document text: ");
+    io::stdout().flush().unwrap();
+
+    let mut doc = String::new();
+    io::stdin().read_line(&mut doc).unwrap();
+    let doc = doc.trim();
+
+    index.append_document(doc);
+    index.save(index_path).expect("failed to save index");
+    println!("Document added and index saved.");
+}
+
+fn add_synonym(index: &mut InvertedIndex, index_path: &Path) {
+    print!("Enter word: ");
+    io::stdout().flush().unwrap();
+    let mut word = String::new();
+    io::stdin().read_line(&mut word).unwrap();
+
+    print!("Enter comma-separated equivalents: ");
+    io::stdout().flush().unwrap();
+    let mut equivalents = String::new();
+    io::stdin().read_line(&mut equivalents).unwrap();
+
+    let equivalents: Vec<String> = equivalents
+        .trim()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    index.add_synonym(word.trim(), equivalents);
+    index.save(index_path).expect("failed to save index");
+    println!("Synonym added and index saved.");
+}
+
 fn show_document(index: &InvertedIndex) {
     print!("Enter document ID: ");
     io::stdout().flush().unwrap();
@@ -103,14 +223,40 @@ fn show_document(index: &InvertedIndex) {
     let mut id_str = String::new();
     io::stdin().read_line(&mut id_str).unwrap();
 
-    match id_str.trim().parse::<usize>() {
-        Ok(id) => {
-            if let Some(doc) = index.get_document(id) {
-                println!("\nDocument {}:\n{}", id, doc);
-            } else {
-                println!("Document {} not found", id);
-            }
-        }
-        Err(_) => println!("Invalid ID format"),
+    let Ok(id) = id_str.trim().parse::<usize>() else {
+        println!("Invalid ID format");
+        return;
+    };
+    let Some(doc) = index.get_document(id) else {
+        println!("Document {} not found", id);
+        return;
+    };
+
+    print!("Highlight query (leave blank to skip): ");
+    io::stdout().flush().unwrap();
+    let mut query = String::new();
+    io::stdin().read_line(&mut query).unwrap();
+    let query = query.trim();
+
+    if query.is_empty() {
+        println!("\nDocument {}:\n{}", id, doc);
+    } else {
+        let spans = index.highlight(id, query);
+        println!("\nDocument {}:\n{}", id, highlighted(doc, &spans));
+    }
+}
+
+/// Wraps each matched span in `**...**` for display in the terminal.
+fn highlighted(text: &str, spans: &[(usize, usize)]) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for &(start, end) in spans {
+        out.push_str(&text[cursor..start]);
+        out.push_str("**");
+        out.push_str(&text[start..end]);
+        out.push_str("**");
+        cursor = end;
     }
+    out.push_str(&text[cursor..]);
+    out
 }
\ No newline at end of file
@@ -0,0 +1,85 @@
+/// Maximum edit distance allowed for a query word of the given length,
+/// following the common typo-tolerance heuristic: short words must match
+/// exactly, longer words tolerate a one-character edit, and long words
+/// tolerate two.
+pub(crate) fn typo_budget(len: usize) -> u8 {
+    if len >= 9 {
+        2
+    } else if len >= 5 {
+        1
+    } else {
+        0
+    }
+}
+
+/// Whether `a` and `b` are within `max_distance` edits of each other, using
+/// the classic row-based Levenshtein DP. The row is tracked incrementally
+/// and the scan is abandoned as soon as the current row's minimum exceeds
+/// `max_distance`, so dictionary words that are obviously too far away are
+/// rejected early.
+pub(crate) fn levenshtein_within(a: &str, b: &str, max_distance: u8) -> bool {
+    let k = max_distance as usize;
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    if a.len().abs_diff(b.len()) > k {
+        return false;
+    }
+
+    let mut prev_row: Vec<usize> = (0..=b.len()).collect();
+
+    for i in 1..=a.len() {
+        let mut row = vec![0usize; b.len() + 1];
+        row[0] = i;
+        let mut row_min = row[0];
+
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            row[j] = (prev_row[j] + 1)
+                .min(row[j - 1] + 1)
+                .min(prev_row[j - 1] + cost);
+            row_min = row_min.min(row[j]);
+        }
+
+        if row_min > k {
+            return false;
+        }
+
+        prev_row = row;
+    }
+
+    prev_row[b.len()] <= k
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_typo_budget_thresholds() {
+        assert_eq!(typo_budget(3), 0);
+        assert_eq!(typo_budget(5), 1);
+        assert_eq!(typo_budget(9), 2);
+    }
+
+    #[test]
+    fn test_levenshtein_within_exact_match() {
+        assert!(levenshtein_within("quick", "quick", 0));
+    }
+
+    #[test]
+    fn test_levenshtein_within_one_edit() {
+        assert!(levenshtein_within("quik", "quick", 1));
+        assert!(!levenshtein_within("quik", "quick", 0));
+    }
+
+    #[test]
+    fn test_levenshtein_within_too_far() {
+        assert!(!levenshtein_within("cat", "dog", 2));
+    }
+
+    #[test]
+    fn test_levenshtein_within_length_mismatch_shortcut() {
+        assert!(!levenshtein_within("a", "abcdef", 1));
+    }
+}
@@ -1,32 +1,232 @@
 use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
 
-use crate::tokenizer::SimpleTokenizer;
+use crate::fuzzy::{levenshtein_within, typo_budget};
+use crate::persist::{read_bytes, read_u32, read_u64, write_bytes, write_u32, write_u64};
+use crate::query::Operation;
+use crate::span_merge::merge_spans;
+use crate::tokenizer::{word_spans, SimpleTokenizer, Tokenizer};
+
+/// Magic bytes identifying a serialized index file, followed by a
+/// single format-version byte.
+const MAGIC: &[u8; 4] = b"IIDX";
+const VERSION: u8 = 2;
 
-#[derive(Default)]
 pub struct InvertedIndex {
-    core: HashMap<String, HashSet<usize>>,
+    core: HashMap<String, HashMap<usize, Vec<u32>>>,
     docs: HashMap<usize, String>,
+    term_freqs: HashMap<usize, HashMap<String, u32>>,
+    synonyms: HashMap<String, Vec<String>>,
     next_doc_id: usize,
+    tokenizer: Box<dyn Tokenizer>,
+}
+
+impl Default for InvertedIndex {
+    fn default() -> Self {
+        Self::new(SimpleTokenizer)
+    }
 }
 
 impl InvertedIndex {
-    pub fn new() -> Self {
-        Self::default()
+    /// Build an index using the given tokenizer for both indexing and
+    /// querying, so terms stay consistent between the two.
+    pub fn new(tokenizer: impl Tokenizer + 'static) -> Self {
+        Self {
+            core: HashMap::new(),
+            docs: HashMap::new(),
+            term_freqs: HashMap::new(),
+            synonyms: HashMap::new(),
+            next_doc_id: 0,
+            tokenizer: Box::new(tokenizer),
+        }
+    }
+
+    /// Register `equivalents` as synonyms of `word`, so a query for
+    /// `word` also matches documents containing any of them. An
+    /// equivalent may be multi-word (e.g. "check engine light"), in
+    /// which case it expands into a phrase/AND group rather than a
+    /// single term.
+    pub fn add_synonym(&mut self, word: &str, equivalents: impl IntoIterator<Item = impl Into<String>>) {
+        self.synonyms
+            .entry(word.to_string())
+            .or_default()
+            .extend(equivalents.into_iter().map(Into::into));
     }
 
     /// Add a document to the inverted index by tokenizing it,
     /// and then expanding out the index, storing the original
-    /// document in its untokenized form
+    /// document in its untokenized form. Each token's position (its
+    /// index among the document's tokens) is recorded alongside the doc
+    /// id, so phrase and proximity queries can later verify adjacency.
     pub fn insert_document(&mut self, doc: &str) {
-        let tokens = SimpleTokenizer::tokenize(doc);
+        let tokens = self.tokenizer.tokenize(doc);
         self.docs.insert(self.next_doc_id, doc.to_string());
-        for token in tokens {
-            let doc_list = self.core.entry(token).or_default();
-            doc_list.insert(self.next_doc_id);
+
+        let mut freqs: HashMap<String, u32> = HashMap::new();
+        for (position, token) in tokens.into_iter().enumerate() {
+            let postings = self.core.entry(token.clone()).or_default();
+            postings
+                .entry(self.next_doc_id)
+                .or_default()
+                .push(position as u32);
+            *freqs.entry(token).or_insert(0) += 1;
         }
+        self.term_freqs.insert(self.next_doc_id, freqs);
+
         self.next_doc_id += 1;
     }
 
+    /// Add a document after reopening a persisted index. Equivalent to
+    /// `insert_document`; the name just makes explicit that this is safe
+    /// to call post-reopen, since `open` restores `next_doc_id` so new
+    /// doc ids continue where the saved index left off.
+    pub fn append_document(&mut self, doc: &str) {
+        self.insert_document(doc);
+    }
+
+    /// Serialize the index to `path` using a compact custom binary
+    /// layout, so it can be reloaded with `open` instead of re-indexing
+    /// the corpus from scratch on every run.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        write_u64(&mut writer, self.next_doc_id as u64)?;
+
+        write_u64(&mut writer, self.docs.len() as u64)?;
+        for (&doc_id, text) in &self.docs {
+            write_u64(&mut writer, doc_id as u64)?;
+            write_bytes(&mut writer, text.as_bytes())?;
+        }
+
+        write_u64(&mut writer, self.core.len() as u64)?;
+        for (term, postings) in &self.core {
+            write_bytes(&mut writer, term.as_bytes())?;
+            write_u64(&mut writer, postings.len() as u64)?;
+            for (&doc_id, positions) in postings {
+                write_u64(&mut writer, doc_id as u64)?;
+                write_u64(&mut writer, positions.len() as u64)?;
+                for &position in positions {
+                    write_u32(&mut writer, position)?;
+                }
+            }
+        }
+
+        write_u64(&mut writer, self.term_freqs.len() as u64)?;
+        for (&doc_id, freqs) in &self.term_freqs {
+            write_u64(&mut writer, doc_id as u64)?;
+            write_u64(&mut writer, freqs.len() as u64)?;
+            for (term, &freq) in freqs {
+                write_bytes(&mut writer, term.as_bytes())?;
+                write_u32(&mut writer, freq)?;
+            }
+        }
+
+        write_u64(&mut writer, self.synonyms.len() as u64)?;
+        for (word, equivalents) in &self.synonyms {
+            write_bytes(&mut writer, word.as_bytes())?;
+            write_u64(&mut writer, equivalents.len() as u64)?;
+            for equivalent in equivalents {
+                write_bytes(&mut writer, equivalent.as_bytes())?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    /// Reconstruct an index previously written by `save`. The tokenizer
+    /// isn't persisted (it isn't data), so the caller supplies the same
+    /// one used when the index was built.
+    ///
+    /// Every count below comes straight from the file and is never trusted
+    /// as a pre-allocation size — a corrupt or truncated file can claim an
+    /// arbitrarily large count, and collections built from it are grown
+    /// incrementally so that case surfaces as the `io::Result::Err` this
+    /// function promises, not an allocation panic.
+    pub fn open(path: &Path, tokenizer: impl Tokenizer + 'static) -> io::Result<Self> {
+        let mut reader = BufReader::new(File::open(path)?);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "not an iindex file"));
+        }
+        let mut version = [0u8; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unsupported iindex format version {}", version[0]),
+            ));
+        }
+
+        let next_doc_id = read_u64(&mut reader)? as usize;
+
+        let doc_count = read_u64(&mut reader)?;
+        let mut docs = HashMap::new();
+        for _ in 0..doc_count {
+            let doc_id = read_u64(&mut reader)? as usize;
+            let text = string_from_bytes(read_bytes(&mut reader)?)?;
+            docs.insert(doc_id, text);
+        }
+
+        let term_count = read_u64(&mut reader)?;
+        let mut core = HashMap::new();
+        for _ in 0..term_count {
+            let term = string_from_bytes(read_bytes(&mut reader)?)?;
+            let posting_count = read_u64(&mut reader)?;
+            let mut postings = HashMap::new();
+            for _ in 0..posting_count {
+                let doc_id = read_u64(&mut reader)? as usize;
+                let position_count = read_u64(&mut reader)?;
+                let mut positions = Vec::new();
+                for _ in 0..position_count {
+                    positions.push(read_u32(&mut reader)?);
+                }
+                postings.insert(doc_id, positions);
+            }
+            core.insert(term, postings);
+        }
+
+        let term_freq_doc_count = read_u64(&mut reader)?;
+        let mut term_freqs = HashMap::new();
+        for _ in 0..term_freq_doc_count {
+            let doc_id = read_u64(&mut reader)? as usize;
+            let freq_count = read_u64(&mut reader)?;
+            let mut freqs = HashMap::new();
+            for _ in 0..freq_count {
+                let term = string_from_bytes(read_bytes(&mut reader)?)?;
+                let freq = read_u32(&mut reader)?;
+                freqs.insert(term, freq);
+            }
+            term_freqs.insert(doc_id, freqs);
+        }
+
+        let synonym_count = read_u64(&mut reader)?;
+        let mut synonyms = HashMap::new();
+        for _ in 0..synonym_count {
+            let word = string_from_bytes(read_bytes(&mut reader)?)?;
+            let equivalent_count = read_u64(&mut reader)?;
+            let mut equivalents = Vec::new();
+            for _ in 0..equivalent_count {
+                equivalents.push(string_from_bytes(read_bytes(&mut reader)?)?);
+            }
+            synonyms.insert(word, equivalents);
+        }
+
+        Ok(Self {
+            core,
+            docs,
+            term_freqs,
+            synonyms,
+            next_doc_id,
+            tokenizer: Box::new(tokenizer),
+        })
+    }
+
     /// Get a document via its id
     pub fn get_document(&self, id: usize) -> Option<&String> {
         self.docs.get(&id)
@@ -35,14 +235,13 @@ impl InvertedIndex {
     /// Perform a search "OR" on the index, returning the
     /// doc id "hits". This means that it will return all documents
     /// that match **at least one** token from the query (more lax).
+    /// Each token is expanded through any registered synonyms first.
     pub fn search_or(&self, query: &str) -> HashSet<usize> {
-        let query_tokens = SimpleTokenizer::tokenize(query);
+        let query_tokens = self.tokenizer.tokenize(query);
         let mut hits = HashSet::new();
 
-        for token in query_tokens {
-            if let Some(doc_ids) = self.core.get(&token) {
-                hits.extend(doc_ids);
-            }
+        for token in &query_tokens {
+            hits.extend(self.search(&Operation::Query(token.clone())));
         }
 
         hits
@@ -51,34 +250,438 @@ impl InvertedIndex {
     /// Perform a search "AND" on the index, returning the
     /// doc id "hits". This means that it will return all documents
     /// that match **all tokens** from the query (more conservative).
+    /// Each token is expanded through any registered synonyms first.
     pub fn search_and(&self, query: &str) -> HashSet<usize> {
-        let query_tokens = SimpleTokenizer::tokenize(query);
+        let query_tokens = self.tokenizer.tokenize(query);
         let mut hits: Option<HashSet<usize>> = None;
 
-        for token in query_tokens {
-            if let Some(doc_ids) = self.core.get(&token) {
-                let doc_set: HashSet<usize> = doc_ids.iter().copied().collect();
-                hits = Some(match hits {
-                    None => doc_set,
-                    Some(current) => current.intersection(&doc_set).copied().collect(),
-                });
-            } else {
-                // token not found in any documents, so no results
+        for token in &query_tokens {
+            let token_hits = self.search(&Operation::Query(token.clone()));
+            if token_hits.is_empty() {
+                // token (and all its synonyms) matched nothing, so no results
                 return HashSet::new();
             }
+            hits = Some(match hits {
+                None => token_hits,
+                Some(current) => current.intersection(&token_hits).copied().collect(),
+            });
         }
 
         hits.unwrap_or_default()
     }
+
+    /// Look up the raw postings for a term, tokenizing it first (atoms
+    /// parsed from a boolean query aren't pre-tokenized). This performs no
+    /// synonym expansion; it's the leaf-level lookup that `synonym_hits`
+    /// bottoms out to for each already-expanded term.
+    fn term_hits(&self, term: &str) -> HashSet<usize> {
+        let mut hits = HashSet::new();
+        for token in self.tokenizer.tokenize(term) {
+            if let Some(postings) = self.core.get(&token) {
+                hits.extend(postings.keys());
+            }
+        }
+        hits
+    }
+
+    /// Union of `term_hits` for `term` and every term it expands to
+    /// through registered synonyms. This is the single place synonym
+    /// expansion feeds into `search`'s `Query` leaves, so every entry
+    /// point built on top of `search` (`search_or`, `search_and`, and
+    /// arbitrary boolean queries from `crate::query::parse_query`) sees
+    /// synonyms uniformly.
+    fn synonym_hits(&self, term: &str) -> HashSet<usize> {
+        let mut expanded_terms = Vec::new();
+        Self::flatten_query_terms(&self.expand_synonyms(term), &mut expanded_terms);
+
+        let mut hits = HashSet::new();
+        for expanded_term in expanded_terms {
+            hits.extend(self.term_hits(&expanded_term));
+        }
+        hits
+    }
+
+    /// Evaluate an arbitrary boolean query tree (see `crate::query`)
+    /// against the index, returning the matching doc ids. `And` nodes
+    /// intersect their children's hits, `Or` nodes union them, and `Not`
+    /// subtracts its child's hits from the full doc-id universe. This
+    /// subsumes `search_or`/`search_and` as two-level special cases.
+    pub fn search(&self, op: &Operation) -> HashSet<usize> {
+        match op {
+            Operation::Query(term) => self.synonym_hits(term),
+            Operation::And(children) => {
+                let mut children = children.iter();
+                let Some(first) = children.next() else {
+                    return HashSet::new();
+                };
+                let mut hits = self.search(first);
+                for child in children {
+                    let next_hits = self.search(child);
+                    hits = hits.intersection(&next_hits).copied().collect();
+                }
+                hits
+            }
+            Operation::Or(children) => {
+                let mut hits = HashSet::new();
+                for child in children {
+                    hits.extend(self.search(child));
+                }
+                hits
+            }
+            Operation::Not(inner) => {
+                let universe: HashSet<usize> = (0..self.next_doc_id).collect();
+                let inner_hits = self.search(inner);
+                universe.difference(&inner_hits).copied().collect()
+            }
+        }
+    }
+
+    /// Rewrite a single query token into an `Or` over itself plus its
+    /// registered synonyms, so searching e.g. "auto" also matches "car".
+    /// A multi-word synonym expands into an `And` group (all its words
+    /// must be present), and each synonym word is itself expanded
+    /// recursively. `seen` guards against cycles (e.g. "auto" <-> "car")
+    /// so expansion always terminates.
+    fn expand_synonyms(&self, token: &str) -> Operation {
+        let mut seen = HashSet::new();
+        self.expand_synonyms_inner(token, &mut seen)
+    }
+
+    fn expand_synonyms_inner(&self, token: &str, seen: &mut HashSet<String>) -> Operation {
+        if !seen.insert(token.to_string()) {
+            return Operation::Query(token.to_string());
+        }
+
+        let mut branches = vec![Operation::Query(token.to_string())];
+        if let Some(equivalents) = self.synonyms.get(token) {
+            for equivalent in equivalents {
+                let words = self.tokenizer.tokenize(equivalent);
+                if words.is_empty() {
+                    continue;
+                }
+                let mut word_ops: Vec<Operation> = words
+                    .iter()
+                    .map(|word| self.expand_synonyms_inner(word, seen))
+                    .collect();
+                branches.push(if word_ops.len() == 1 {
+                    word_ops.pop().unwrap()
+                } else {
+                    Operation::And(word_ops)
+                });
+            }
+        }
+        seen.remove(token);
+
+        if branches.len() == 1 {
+            branches.pop().unwrap()
+        } else {
+            Operation::Or(branches)
+        }
+    }
+
+    /// Perform a ranked search using TF-IDF, returning doc id/score
+    /// pairs sorted by descending relevance (ties broken by doc id).
+    ///
+    /// Each query term (and, transitively, its registered synonyms)
+    /// contributes `(1 + ln(tf)) * idf` to a candidate document's score,
+    /// where `idf = ln(N / df_t)`. Scores are then cosine-normalized by
+    /// the document's full term-weight vector so longer documents don't
+    /// win purely on length.
+    pub fn search_ranked(&self, query: &str) -> Vec<(usize, f64)> {
+        let query_tokens = self.tokenizer.tokenize(query);
+        let total_docs = self.docs.len();
+        if total_docs == 0 {
+            return Vec::new();
+        }
+
+        // A synonym expansion is a graph, not necessarily a tree (e.g. a
+        // cycle, or a term reachable through more than one synonym path),
+        // so the same term can appear more than once in `terms` here.
+        // Dedupe before scoring or a term reachable via N paths would have
+        // its TF-IDF contribution counted N times.
+        let mut terms = HashSet::new();
+        for token in &query_tokens {
+            Self::flatten_query_terms(&self.expand_synonyms(token), &mut terms);
+        }
+
+        let mut scores: HashMap<usize, f64> = HashMap::new();
+        for term in terms {
+            self.accumulate_term_score(&term, total_docs, &mut scores);
+        }
+
+        let mut ranked: Vec<(usize, f64)> = scores
+            .into_iter()
+            .map(|(doc_id, score)| {
+                let norm = self.doc_norm(doc_id);
+                let score = if norm > 0.0 { score / norm } else { score };
+                (doc_id, score)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+
+        ranked
+    }
+
+    /// Typo-tolerant search: for each query token, gather the union of
+    /// postings of every dictionary term within `max_distance` edits (capped
+    /// by the dictionary term's own typo budget, so short terms still
+    /// require an exact match). This makes search robust to misspellings
+    /// like "quik" matching "quick".
+    pub fn search_fuzzy(&self, query: &str, max_distance: u8) -> HashSet<usize> {
+        let query_tokens = self.tokenizer.tokenize(query);
+        let mut hits = HashSet::new();
+
+        for token in &query_tokens {
+            for (term, postings) in &self.core {
+                let budget = max_distance.min(typo_budget(term.chars().count()));
+                if levenshtein_within(token, term, budget) {
+                    hits.extend(postings.keys());
+                }
+            }
+        }
+
+        hits
+    }
+
+    /// Search for an exact phrase, returning docs where the phrase's
+    /// tokens occur at consecutive positions (for tokens `t0..tn`, a doc
+    /// matches if there exist positions `p` with `t0@p, t1@p+1, ...`).
+    pub fn search_phrase(&self, phrase: &str) -> HashSet<usize> {
+        let tokens = self.tokenizer.tokenize(phrase);
+        let Some(postings_per_token) = self.postings_for_all(&tokens) else {
+            return HashSet::new();
+        };
+
+        Self::candidate_docs(&postings_per_token)
+            .into_iter()
+            .filter(|&doc_id| Self::has_consecutive_positions(&postings_per_token, doc_id))
+            .collect()
+    }
+
+    /// Search for docs where every query term appears within a sliding
+    /// window of `window` token positions of each other.
+    pub fn search_proximity(&self, query: &str, window: usize) -> HashSet<usize> {
+        let tokens = self.tokenizer.tokenize(query);
+        let Some(postings_per_token) = self.postings_for_all(&tokens) else {
+            return HashSet::new();
+        };
+
+        Self::candidate_docs(&postings_per_token)
+            .into_iter()
+            .filter(|&doc_id| Self::has_proximity_window(&postings_per_token, doc_id, window))
+            .collect()
+    }
+
+    /// Look up the postings for each of `tokens`, returning `None` if any
+    /// token isn't in the index (phrase/proximity can't match in that case).
+    fn postings_for_all(&self, tokens: &[String]) -> Option<Vec<&HashMap<usize, Vec<u32>>>> {
+        if tokens.is_empty() {
+            return None;
+        }
+
+        tokens.iter().map(|token| self.core.get(token)).collect()
+    }
+
+    /// Doc ids that contain every token, i.e. the intersection of each
+    /// token's posting list.
+    fn candidate_docs(postings_per_token: &[&HashMap<usize, Vec<u32>>]) -> HashSet<usize> {
+        let mut candidates: HashSet<usize> = postings_per_token[0].keys().copied().collect();
+        for postings in &postings_per_token[1..] {
+            let doc_set: HashSet<usize> = postings.keys().copied().collect();
+            candidates = candidates.intersection(&doc_set).copied().collect();
+        }
+        candidates
+    }
+
+    /// Whether `doc_id` has the tokens at consecutive positions, i.e. some
+    /// starting position `p` where token `i` occurs at `p + i` for every i.
+    fn has_consecutive_positions(postings_per_token: &[&HashMap<usize, Vec<u32>>], doc_id: usize) -> bool {
+        let Some(first_positions) = postings_per_token[0].get(&doc_id) else {
+            return false;
+        };
+
+        'starts: for &start in first_positions {
+            for (offset, postings) in postings_per_token.iter().enumerate().skip(1) {
+                let Some(positions) = postings.get(&doc_id) else {
+                    continue 'starts;
+                };
+                if !positions.contains(&(start + offset as u32)) {
+                    continue 'starts;
+                }
+            }
+            return true;
+        }
+
+        false
+    }
+
+    /// Whether `doc_id` has an occurrence of every token within a sliding
+    /// window of `window` token positions, via a two-pointer scan over the
+    /// doc's merged, sorted occurrence list.
+    fn has_proximity_window(
+        postings_per_token: &[&HashMap<usize, Vec<u32>>],
+        doc_id: usize,
+        window: usize,
+    ) -> bool {
+        // A window of 0 can never be satisfied (the span between the first
+        // and last occurrence is always at least 1), so bail out before the
+        // sliding window below, which assumes `window >= 1` and would
+        // otherwise walk `left` past `right`.
+        if window == 0 {
+            return false;
+        }
+
+        let mut occurrences: Vec<(u32, usize)> = Vec::new();
+        for (term_index, postings) in postings_per_token.iter().enumerate() {
+            let Some(positions) = postings.get(&doc_id) else {
+                return false;
+            };
+            occurrences.extend(positions.iter().map(|&position| (position, term_index)));
+        }
+        occurrences.sort_by_key(|&(position, _)| position);
+
+        let term_count = postings_per_token.len();
+        let mut counts = vec![0usize; term_count];
+        let mut distinct = 0;
+        let mut left = 0;
+
+        for right in 0..occurrences.len() {
+            let term_r = occurrences[right].1;
+            if counts[term_r] == 0 {
+                distinct += 1;
+            }
+            counts[term_r] += 1;
+
+            while occurrences[right].0 - occurrences[left].0 + 1 > window as u32 {
+                let term_l = occurrences[left].1;
+                counts[term_l] -= 1;
+                if counts[term_l] == 0 {
+                    distinct -= 1;
+                }
+                left += 1;
+            }
+
+            if distinct == term_count {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Byte ranges in `doc_id`'s original (untokenized) text where a term
+    /// from `query` occurs, merged into non-overlapping spans sorted by
+    /// start. Matching goes through the same synonym expansion as
+    /// `search_or`/`search_and`, plus a typo budget like `search_fuzzy`, so
+    /// the caller can highlight exactly what a search would have found.
+    pub fn highlight(&self, doc_id: usize, query: &str) -> Vec<(usize, usize)> {
+        let Some(text) = self.docs.get(&doc_id) else {
+            return Vec::new();
+        };
+
+        let mut match_terms = Vec::new();
+        for token in self.tokenizer.tokenize(query) {
+            Self::flatten_query_terms(&self.expand_synonyms(&token), &mut match_terms);
+        }
+
+        let mut spans = Vec::new();
+        for (word, start, end) in word_spans(text) {
+            let doc_tokens = self.tokenizer.tokenize(word);
+            let is_match = doc_tokens.iter().any(|doc_token| {
+                let budget = typo_budget(doc_token.chars().count());
+                match_terms.iter().any(|term| levenshtein_within(doc_token, term, budget))
+            });
+            if is_match {
+                spans.push((start, end));
+            }
+        }
+
+        merge_spans(spans)
+    }
+
+    /// Collect the leaf term strings out of a synonym-expansion tree, so
+    /// `search_ranked` can score each contributing term independently
+    /// rather than walking the tree's And/Or/Not structure itself. `Not`
+    /// has no place in a synonym expansion, so it contributes nothing.
+    /// A synonym graph can reach the same term by more than one path, so a
+    /// `Vec` will contain duplicates; pass a `HashSet` when each term must
+    /// be counted only once (e.g. before TF-IDF scoring).
+    fn flatten_query_terms(op: &Operation, out: &mut impl Extend<String>) {
+        match op {
+            Operation::Query(term) => out.extend(std::iter::once(term.clone())),
+            Operation::And(children) | Operation::Or(children) => {
+                for child in children {
+                    Self::flatten_query_terms(child, out);
+                }
+            }
+            Operation::Not(_) => {}
+        }
+    }
+
+    /// Add `term`'s TF-IDF contribution to each of its matching documents'
+    /// running `scores`, used by `search_ranked`.
+    fn accumulate_term_score(&self, term: &str, total_docs: usize, scores: &mut HashMap<usize, f64>) {
+        let Some(postings) = self.core.get(term) else {
+            return;
+        };
+        let idf = (total_docs as f64 / postings.len() as f64).ln();
+
+        for &doc_id in postings.keys() {
+            let tf = self
+                .term_freqs
+                .get(&doc_id)
+                .and_then(|freqs| freqs.get(term))
+                .copied()
+                .unwrap_or(0);
+            if tf == 0 {
+                continue;
+            }
+            let weight = (1.0 + (tf as f64).ln()) * idf;
+            *scores.entry(doc_id).or_insert(0.0) += weight;
+        }
+    }
+
+    /// Cosine length of a document's full TF-IDF weight vector, used to
+    /// normalize `search_ranked` scores.
+    fn doc_norm(&self, doc_id: usize) -> f64 {
+        let total_docs = self.docs.len();
+        let Some(freqs) = self.term_freqs.get(&doc_id) else {
+            return 0.0;
+        };
+
+        let sum_sq: f64 = freqs
+            .iter()
+            .map(|(term, &tf)| {
+                let df = self.core.get(term).map(|postings| postings.len()).unwrap_or(1);
+                let idf = (total_docs as f64 / df as f64).ln();
+                let weight = (1.0 + (tf as f64).ln()) * idf;
+                weight * weight
+            })
+            .sum();
+
+        sum_sq.sqrt()
+    }
+}
+
+/// Decode a length-prefixed string read by `open`, surfacing invalid
+/// UTF-8 as an `io::Error` rather than panicking on a corrupt file.
+fn string_from_bytes(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::query::parse_query;
 
     #[test]
     fn test_insert_and_retrieve_document() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
 
         assert_eq!(index.get_document(0), Some(&"hello world".to_string()));
@@ -86,7 +689,7 @@ mod tests {
 
     #[test]
     fn test_multiple_documents() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("foo bar");
         index.insert_document("hello foo");
@@ -98,7 +701,7 @@ mod tests {
 
     #[test]
     fn test_search_or_single_match() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("foo bar");
 
@@ -108,7 +711,7 @@ mod tests {
 
     #[test]
     fn test_search_or_multiple_matches() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("hello foo");
         index.insert_document("bar baz");
@@ -121,7 +724,7 @@ mod tests {
 
     #[test]
     fn test_search_or_multiple_tokens() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("foo bar");
         index.insert_document("baz qux");
@@ -134,7 +737,7 @@ mod tests {
 
     #[test]
     fn test_search_or_no_matches() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
 
         let results = index.search_or("notfound");
@@ -143,7 +746,7 @@ mod tests {
 
     #[test]
     fn test_search_and_all_tokens_present() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("hello foo world");
         index.insert_document("foo bar");
@@ -156,7 +759,7 @@ mod tests {
 
     #[test]
     fn test_search_and_partial_match() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("hello foo");
         index.insert_document("world bar");
@@ -168,7 +771,7 @@ mod tests {
 
     #[test]
     fn test_search_and_no_matches() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("foo bar");
 
@@ -178,7 +781,7 @@ mod tests {
 
     #[test]
     fn test_search_and_single_token() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("hello world");
         index.insert_document("foo bar");
 
@@ -189,7 +792,7 @@ mod tests {
 
     #[test]
     fn test_case_insensitive_search() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("Hello World");
 
         let results = index.search_or("hello");
@@ -198,11 +801,415 @@ mod tests {
 
     #[test]
     fn test_punctuation_removed() {
-        let mut index = InvertedIndex::new();
+        let mut index = InvertedIndex::new(SimpleTokenizer);
         index.insert_document("Hello, World!");
 
         let results = index.search_or("hello world");
         assert_eq!(results.len(), 1);
         assert!(results.contains(&0));
     }
+
+    #[test]
+    fn test_search_ranked_orders_by_relevance() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+        index.insert_document("the quick quick quick fox jumps");
+        index.insert_document("a lazy dog sleeps");
+
+        let results = index.search_ranked("quick fox");
+        let ids: Vec<usize> = results.iter().map(|(id, _)| *id).collect();
+
+        assert_eq!(ids, vec![1, 0]);
+        assert!(results[0].1 > results[1].1);
+    }
+
+    #[test]
+    fn test_search_ranked_no_matches() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+
+        let results = index.search_ranked("notfound");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_ranked_empty_index() {
+        let index = InvertedIndex::new(SimpleTokenizer);
+        let results = index.search_ranked("hello");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_ranked_ties_break_by_doc_id() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("hello world");
+
+        let results = index.search_ranked("hello");
+        assert_eq!(results.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_search_tree_and() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("hello foo");
+
+        let results = index.search(&parse_query("hello AND world").unwrap());
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_tree_or() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("foo bar");
+        index.insert_document("baz qux");
+
+        let results = index.search(&parse_query("hello OR foo").unwrap());
+        assert_eq!(results, vec![0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_tree_not() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("hello foo");
+
+        let results = index.search(&parse_query("hello NOT foo").unwrap());
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_tree_nested() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("hello fox");
+        index.insert_document("hello lazy");
+
+        let results = index.search(&parse_query("hello AND (world OR fox) NOT lazy").unwrap());
+        assert_eq!(results, vec![0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_tree_expands_synonyms() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("i need a new car");
+        index.insert_document("the auto mechanic is closed today");
+        index.insert_document("the weather is nice today");
+        index.add_synonym("car", ["auto"]);
+
+        let results = index.search(&parse_query("car AND today").unwrap());
+        assert_eq!(results, vec![1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_fuzzy_typo() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        let results = index.search_fuzzy("quik", 1);
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_fuzzy_exact_match_still_works() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+
+        let results = index.search_fuzzy("hello", 1);
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_fuzzy_too_far_no_match() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+
+        let results = index.search_fuzzy("goodbye", 1);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_fuzzy_short_words_require_exact_match() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("cat dog");
+
+        let results = index.search_fuzzy("cot", 1);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_phrase_matches_consecutive_tokens() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox jumps");
+        index.insert_document("the fox is quick but brown");
+
+        let results = index.search_phrase("brown fox");
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_phrase_no_match_when_tokens_not_adjacent() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        let results = index.search_phrase("quick fox");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_phrase_unknown_term_no_match() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        let results = index.search_phrase("brown zebra");
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_proximity_within_window() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox dog runs");
+        index.insert_document("quick word word word word word word word dog");
+
+        let results = index.search_proximity("quick dog", 5);
+        assert_eq!(results, vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_search_proximity_outside_window_excluded() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("quick brown animal word word word word word dog");
+
+        let results = index.search_proximity("quick dog", 3);
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_search_proximity_zero_window_returns_no_matches() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        assert!(index.search_proximity("quick", 0).is_empty());
+        assert!(index.search_proximity("quick fox", 0).is_empty());
+    }
+
+    #[test]
+    fn test_save_and_open_round_trip() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+        index.insert_document("the quick brown fox");
+
+        let path = std::env::temp_dir().join("iindex_test_save_and_open_round_trip.bin");
+        index.save(&path).unwrap();
+
+        let reopened = InvertedIndex::open(&path, SimpleTokenizer).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(reopened.get_document(0), Some(&"hello world".to_string()));
+        assert_eq!(reopened.get_document(1), Some(&"the quick brown fox".to_string()));
+        assert_eq!(reopened.search_and("hello world"), vec![0].into_iter().collect());
+        assert_eq!(reopened.search_phrase("brown fox"), vec![1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_append_document_after_reopen_continues_doc_ids() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("hello world");
+
+        let path = std::env::temp_dir().join("iindex_test_append_document_after_reopen.bin");
+        index.save(&path).unwrap();
+
+        let mut reopened = InvertedIndex::open(&path, SimpleTokenizer).unwrap();
+        reopened.append_document("foo bar");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(reopened.get_document(0), Some(&"hello world".to_string()));
+        assert_eq!(reopened.get_document(1), Some(&"foo bar".to_string()));
+        assert_eq!(reopened.search_or("foo"), vec![1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_open_rejects_non_iindex_file() {
+        let path = std::env::temp_dir().join("iindex_test_open_rejects_non_iindex_file.bin");
+        std::fs::write(&path, b"not an index").unwrap();
+
+        let result = InvertedIndex::open(&path, SimpleTokenizer);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_open_rejects_corrupt_file_with_bogus_count_instead_of_panicking() {
+        let path = std::env::temp_dir().join("iindex_test_open_rejects_corrupt_count.bin");
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        write_u64(&mut bytes, 0).unwrap(); // next_doc_id
+        write_u64(&mut bytes, u64::MAX / 2).unwrap(); // bogus doc_count
+        std::fs::write(&path, &bytes).unwrap();
+
+        let result = InvertedIndex::open(&path, SimpleTokenizer);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_open_rejects_unsupported_version() {
+        let path = std::env::temp_dir().join("iindex_test_open_rejects_unsupported_version.bin");
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION + 1);
+        std::fs::write(&path, &bytes).unwrap();
+
+        let result = InvertedIndex::open(&path, SimpleTokenizer);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_synonym_expands_or_search() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("i need a new car");
+        index.insert_document("the auto mechanic is closed today");
+        index.add_synonym("car", ["auto"]);
+
+        assert_eq!(index.search_or("car"), vec![0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_synonym_expands_and_search() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("buy a new car today");
+        index.insert_document("buy an auto today");
+        index.add_synonym("car", ["auto"]);
+
+        assert_eq!(index.search_and("buy car today"), vec![0, 1].into_iter().collect());
+    }
+
+    #[test]
+    fn test_synonym_expands_multi_word_equivalent() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the big apple is busy");
+        index.add_synonym("nyc", ["big apple"]);
+
+        assert_eq!(index.search_or("nyc"), vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_synonym_does_not_affect_unrelated_terms() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the auto mechanic is closed");
+        index.add_synonym("car", ["auto"]);
+
+        assert_eq!(index.search_or("truck"), HashSet::new());
+    }
+
+    #[test]
+    fn test_synonym_cycle_does_not_infinite_loop() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("i drive a car");
+        index.add_synonym("car", ["auto"]);
+        index.add_synonym("auto", ["car"]);
+
+        assert_eq!(index.search_or("car"), vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_synonym_boosts_ranked_search() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("car car car");
+        index.insert_document("auto auto auto");
+        index.add_synonym("car", ["auto"]);
+
+        let results = index.search_ranked("car");
+        let ids: Vec<usize> = results.iter().map(|&(id, _)| id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&0) && ids.contains(&1));
+    }
+
+    #[test]
+    fn test_search_ranked_does_not_double_count_diamond_synonym_paths() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("auto auto auto");
+        index.insert_document("vehicle vehicle vehicle");
+        index.add_synonym("car", ["auto", "vehicle"]);
+        index.add_synonym("vehicle", ["auto"]);
+
+        let results = index.search_ranked("car");
+        let scores: HashMap<usize, f64> = results.into_iter().collect();
+
+        // Doc 0 ("auto") is reachable from "car" by two paths (directly,
+        // and via "vehicle"); doc 1 ("vehicle") is reachable by only one.
+        // Both contain the same term 3 times, so they must score equally.
+        assert_eq!(scores[&0], scores[&1]);
+    }
+
+    #[test]
+    fn test_synonyms_persist_across_save_and_open() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the auto mechanic is closed");
+        index.add_synonym("car", ["auto"]);
+
+        let path = std::env::temp_dir().join("iindex_test_synonyms_persist.bin");
+        index.save(&path).unwrap();
+
+        let reopened = InvertedIndex::open(&path, SimpleTokenizer).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(reopened.search_or("car"), vec![0].into_iter().collect());
+    }
+
+    #[test]
+    fn test_highlight_returns_matched_spans() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        assert_eq!(index.highlight(0, "fox"), vec![(16, 19)]);
+    }
+
+    #[test]
+    fn test_highlight_returns_multiple_spans_sorted_by_start() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("quick fox jumps over the fox");
+
+        assert_eq!(index.highlight(0, "quick fox"), vec![(0, 5), (6, 9), (25, 28)]);
+    }
+
+    #[test]
+    fn test_highlight_expands_synonyms() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the auto mechanic is closed");
+        index.add_synonym("car", ["auto"]);
+
+        assert_eq!(index.highlight(0, "car"), vec![(4, 8)]);
+    }
+
+    #[test]
+    fn test_highlight_tolerates_typos() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        assert_eq!(index.highlight(0, "quik"), vec![(4, 9)]);
+    }
+
+    #[test]
+    fn test_highlight_no_match_returns_empty() {
+        let mut index = InvertedIndex::new(SimpleTokenizer);
+        index.insert_document("the quick brown fox");
+
+        assert_eq!(index.highlight(0, "elephant"), Vec::new());
+    }
+
+    #[test]
+    fn test_highlight_unknown_document_returns_empty() {
+        let index = InvertedIndex::new(SimpleTokenizer);
+        assert_eq!(index.highlight(0, "anything"), Vec::new());
+    }
 }
@@ -0,0 +1,6 @@
+mod fuzzy;
+pub mod iindex;
+mod persist;
+pub mod query;
+mod span_merge;
+pub mod tokenizer;
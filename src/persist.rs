@@ -0,0 +1,81 @@
+use std::io::{self, Read, Write};
+
+pub(crate) fn write_u32(writer: &mut impl Write, value: u32) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64(writer: &mut impl Write, value: u64) -> io::Result<()> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+/// Write a length-prefixed byte string.
+pub(crate) fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    write_u64(writer, bytes.len() as u64)?;
+    writer.write_all(bytes)
+}
+
+pub(crate) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+pub(crate) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut buf = [0u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Read a length-prefixed byte string written by `write_bytes`.
+///
+/// The length comes straight from untrusted file data, so it's never used
+/// to pre-allocate a buffer up front: `take(len)` bounds how much is ever
+/// read, and a short read (the file was truncated or the length was bogus)
+/// surfaces as an `UnexpectedEof` error instead of an allocation panic.
+pub(crate) fn read_bytes(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = read_u64(reader)?;
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated length-prefixed byte string",
+        ));
+    }
+    Ok(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u32_round_trip() {
+        let mut buf = Vec::new();
+        write_u32(&mut buf, 4_242).unwrap();
+        assert_eq!(read_u32(&mut &buf[..]).unwrap(), 4_242);
+    }
+
+    #[test]
+    fn test_u64_round_trip() {
+        let mut buf = Vec::new();
+        write_u64(&mut buf, u64::MAX).unwrap();
+        assert_eq!(read_u64(&mut &buf[..]).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let mut buf = Vec::new();
+        write_bytes(&mut buf, b"hello world").unwrap();
+        assert_eq!(read_bytes(&mut &buf[..]).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_read_bytes_rejects_bogus_length_without_panicking() {
+        let mut buf = Vec::new();
+        write_u64(&mut buf, u64::MAX / 2).unwrap();
+        buf.extend_from_slice(b"not that many bytes");
+
+        assert!(read_bytes(&mut &buf[..]).is_err());
+    }
+}
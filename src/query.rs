@@ -0,0 +1,258 @@
+/// A boolean query tree node, combining simple term queries with
+/// `And`/`Or`/`Not`, mirroring MeiliSearch's query tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    And(Vec<Operation>),
+    Or(Vec<Operation>),
+    Not(Box<Operation>),
+    Query(String),
+}
+
+/// Parse a query string such as `hello AND (world OR fox) NOT lazy` into
+/// an `Operation` tree. `AND`/`OR`/`NOT` are matched case-insensitively,
+/// `NOT` binds tighter than `AND`, which binds tighter than `OR`, and
+/// parentheses group sub-expressions. Anything else is treated as a term;
+/// terms with no connective between them (`"hello world"`) are treated as
+/// an implicit `AND`, same as most search engines. Returns an error if the
+/// input doesn't fully parse, e.g. unbalanced parentheses.
+pub fn parse_query(input: &str) -> Result<Operation, String> {
+    let tokens = tokenize(input);
+    let mut parser = Parser { tokens, pos: 0, error: None };
+    let op = parser.parse_or();
+    if let Some(error) = parser.error {
+        return Err(error);
+    }
+    if parser.pos != parser.tokens.len() {
+        return Err(format!(
+            "unexpected token {:?} in query",
+            parser.tokens[parser.pos]
+        ));
+    }
+    Ok(op)
+}
+
+fn tokenize(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    for c in input.chars() {
+        match c {
+            '(' | ')' => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+
+    tokens
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    pos: usize,
+    error: Option<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.pos).map(String::as_str)
+    }
+
+    fn advance(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_keyword(token: &str, keyword: &str) -> bool {
+        token.eq_ignore_ascii_case(keyword)
+    }
+
+    fn parse_or(&mut self) -> Operation {
+        let mut parts = vec![self.parse_and()];
+        while let Some(token) = self.peek() {
+            if Self::is_keyword(token, "OR") {
+                self.advance();
+                parts.push(self.parse_and());
+            } else {
+                break;
+            }
+        }
+        if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            Operation::Or(parts)
+        }
+    }
+
+    fn parse_and(&mut self) -> Operation {
+        let mut parts = vec![self.parse_unary()];
+        while let Some(token) = self.peek() {
+            if Self::is_keyword(token, "AND") {
+                self.advance();
+                parts.push(self.parse_unary());
+            } else if Self::is_keyword(token, "NOT") {
+                self.advance();
+                parts.push(Operation::Not(Box::new(self.parse_unary())));
+            } else if Self::is_keyword(token, "OR") || token == ")" {
+                break;
+            } else {
+                // No recognized connective between terms - treat adjacent
+                // terms as an implicit AND, e.g. "hello world" behaves like
+                // "hello AND world", so no term is silently dropped.
+                parts.push(self.parse_unary());
+            }
+        }
+        if parts.len() == 1 {
+            parts.pop().unwrap()
+        } else {
+            Operation::And(parts)
+        }
+    }
+
+    fn parse_unary(&mut self) -> Operation {
+        if let Some(token) = self.peek() {
+            if Self::is_keyword(token, "NOT") {
+                self.advance();
+                return Operation::Not(Box::new(self.parse_unary()));
+            }
+        }
+        self.parse_atom()
+    }
+
+    fn parse_atom(&mut self) -> Operation {
+        match self.advance() {
+            Some(token) if token == "(" => {
+                let inner = self.parse_or();
+                if matches!(self.peek(), Some(")")) {
+                    self.advance();
+                } else if self.error.is_none() {
+                    self.error = Some("missing closing ')' in query".to_string());
+                }
+                inner
+            }
+            Some(token) => Operation::Query(token),
+            None => Operation::And(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_single_term() {
+        assert_eq!(parse_query("hello").unwrap(), Operation::Query("hello".to_string()));
+    }
+
+    #[test]
+    fn test_parse_and() {
+        assert_eq!(
+            parse_query("hello AND world").unwrap(),
+            Operation::And(vec![
+                Operation::Query("hello".to_string()),
+                Operation::Query("world".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_or() {
+        assert_eq!(
+            parse_query("hello OR world").unwrap(),
+            Operation::Or(vec![
+                Operation::Query("hello".to_string()),
+                Operation::Query("world".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_not() {
+        assert_eq!(
+            parse_query("hello NOT world").unwrap(),
+            Operation::And(vec![
+                Operation::Query("hello".to_string()),
+                Operation::Not(Box::new(Operation::Query("world".to_string()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_nested_parens() {
+        let op = parse_query("hello AND (world OR fox) NOT lazy").unwrap();
+        assert_eq!(
+            op,
+            Operation::And(vec![
+                Operation::Query("hello".to_string()),
+                Operation::Or(vec![
+                    Operation::Query("world".to_string()),
+                    Operation::Query("fox".to_string()),
+                ]),
+                Operation::Not(Box::new(Operation::Query("lazy".to_string()))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_or_has_lower_precedence_than_and() {
+        let op = parse_query("a AND b OR c").unwrap();
+        assert_eq!(
+            op,
+            Operation::Or(vec![
+                Operation::And(vec![
+                    Operation::Query("a".to_string()),
+                    Operation::Query("b".to_string()),
+                ]),
+                Operation::Query("c".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_implicit_and_between_adjacent_terms() {
+        assert_eq!(
+            parse_query("hello world").unwrap(),
+            Operation::And(vec![
+                Operation::Query("hello".to_string()),
+                Operation::Query("world".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_implicit_and_mixes_with_or() {
+        assert_eq!(
+            parse_query("hello world OR fox").unwrap(),
+            Operation::Or(vec![
+                Operation::And(vec![
+                    Operation::Query("hello".to_string()),
+                    Operation::Query("world".to_string()),
+                ]),
+                Operation::Query("fox".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_unbalanced_opening_paren() {
+        assert!(parse_query("hello AND (world OR fox").is_err());
+    }
+
+    #[test]
+    fn test_parse_rejects_unbalanced_closing_paren() {
+        assert!(parse_query("hello)) world").is_err());
+    }
+}
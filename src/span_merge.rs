@@ -0,0 +1,44 @@
+//! Merges overlapping match spans into a non-overlapping, start-sorted
+//! set, as used by `InvertedIndex::highlight`. Spans are half-open byte
+//! ranges `[start, end)` into the original text.
+//!
+//! This is a plain sort-and-sweep, not a tree: the single call site only
+//! ever merges a one-shot batch of spans, so there's no repeated-query
+//! workload that would justify an augmented-tree structure.
+
+pub(crate) fn merge_spans(mut spans: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    spans.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= *last_end => {
+                if end > *last_end {
+                    *last_end = end;
+                }
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merges_overlapping_spans() {
+        assert_eq!(merge_spans(vec![(0, 5), (3, 8)]), vec![(0, 8)]);
+    }
+
+    #[test]
+    fn test_keeps_disjoint_spans_separate() {
+        assert_eq!(merge_spans(vec![(10, 15), (0, 5)]), vec![(0, 5), (10, 15)]);
+    }
+
+    #[test]
+    fn test_merges_adjacent_touching_spans() {
+        assert_eq!(merge_spans(vec![(0, 5), (5, 9)]), vec![(0, 9)]);
+    }
+}
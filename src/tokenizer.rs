@@ -1,9 +1,25 @@
+use std::collections::HashSet;
+
+/// Splits raw text into index terms. Implemented by the baseline
+/// `SimpleTokenizer` and by `TokenizerPipeline`, which layers filter
+/// stages (stop-word removal, stemming, ...) on top of a base tokenizer.
+pub trait Tokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String>;
+}
+
+/// Transforms an already-tokenized stream, e.g. dropping stop words or
+/// stemming terms down to a root form.
+pub trait TokenFilter {
+    fn apply(&self, tokens: Vec<String>) -> Vec<String>;
+}
+
+/// The baseline tokenizer: lowercases, replaces non-alphanumeric
+/// characters with whitespace, and splits on whitespace.
 pub struct SimpleTokenizer;
 
-impl SimpleTokenizer {
-    pub fn tokenize(text: &str) -> Vec<String> {
-        text
-            .to_lowercase()
+impl Tokenizer for SimpleTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.to_lowercase()
             .chars()
             .map(|c| if c.is_alphanumeric() || c.is_whitespace() { c } else { ' ' })
             .collect::<String>()
@@ -13,13 +29,191 @@ impl SimpleTokenizer {
     }
 }
 
+/// Drops tokens found in a configurable stop-word set, e.g. "the", "a",
+/// "is", which otherwise bloat the index without adding retrieval value.
+pub struct StopWordFilter {
+    stop_words: HashSet<String>,
+}
+
+impl StopWordFilter {
+    pub fn new<I, S>(stop_words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            stop_words: stop_words.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// A small set of common English stop words.
+    pub fn english() -> Self {
+        Self::new([
+            "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "in", "into", "is",
+            "it", "no", "not", "of", "on", "or", "such", "that", "the", "their", "then", "there",
+            "these", "they", "this", "to", "was", "will", "with",
+        ])
+    }
+}
+
+impl TokenFilter for StopWordFilter {
+    fn apply(&self, tokens: Vec<String>) -> Vec<String> {
+        tokens
+            .into_iter()
+            .filter(|token| !self.stop_words.contains(token))
+            .collect()
+    }
+}
+
+/// A lightweight Porter-style stemmer: strips common suffixes so
+/// morphological variants of a word (e.g. "jumps"/"jumping"/"jumped")
+/// collapse to a single index term ("jump").
+pub struct PorterStemmer;
+
+impl PorterStemmer {
+    fn stem(word: &str) -> String {
+        const SUFFIXES: &[&str] = &["ational", "ization", "fulness", "ing", "edly", "ed", "ies", "es", "s"];
+
+        for suffix in SUFFIXES {
+            if let Some(stem) = word.strip_suffix(suffix) {
+                // Keep at least a 3-character stem so short words like
+                // "is", "as", or "bus" aren't hollowed out.
+                if stem.chars().count() >= 3 {
+                    return stem.to_string();
+                }
+            }
+        }
+
+        word.to_string()
+    }
+}
+
+impl TokenFilter for PorterStemmer {
+    fn apply(&self, tokens: Vec<String>) -> Vec<String> {
+        tokens.into_iter().map(|token| Self::stem(&token)).collect()
+    }
+}
+
+/// Composes a base tokenizer with an ordered list of filter stages, so
+/// index-time and query-time tokenization always run the exact same
+/// pipeline.
+pub struct TokenizerPipeline {
+    base: Box<dyn Tokenizer>,
+    filters: Vec<Box<dyn TokenFilter>>,
+}
+
+impl TokenizerPipeline {
+    pub fn new(base: impl Tokenizer + 'static) -> Self {
+        Self {
+            base: Box::new(base),
+            filters: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: impl TokenFilter + 'static) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+}
+
+impl Default for TokenizerPipeline {
+    fn default() -> Self {
+        Self::new(SimpleTokenizer)
+    }
+}
+
+impl Tokenizer for TokenizerPipeline {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        let mut tokens = self.base.tokenize(text);
+        for filter in &self.filters {
+            tokens = filter.apply(tokens);
+        }
+        tokens
+    }
+}
+
+/// Splits text into raw words along with their byte ranges, using the same
+/// alphanumeric-run rule as `SimpleTokenizer`. Used by highlighting to map a
+/// matched token back to its location in the original, untokenized text.
+pub(crate) fn word_spans(text: &str) -> Vec<(&str, usize, usize)> {
+    let mut spans = Vec::new();
+    let mut start = None;
+    let mut last_end = 0;
+
+    for (i, c) in text.char_indices() {
+        if c.is_alphanumeric() {
+            if start.is_none() {
+                start = Some(i);
+            }
+            last_end = i + c.len_utf8();
+        } else if let Some(s) = start.take() {
+            spans.push((&text[s..last_end], s, last_end));
+        }
+    }
+    if let Some(s) = start {
+        spans.push((&text[s..last_end], s, last_end));
+    }
+
+    spans
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_tokenize_basic() {
-        let tokens = SimpleTokenizer::tokenize("Hello, world!");
+        let tokens = SimpleTokenizer.tokenize("Hello, world!");
         assert_eq!(tokens, vec!["hello", "world"])
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_stop_word_filter_removes_configured_words() {
+        let filter = StopWordFilter::new(["the", "a"]);
+        let tokens = filter.apply(vec!["the".into(), "quick".into(), "a".into(), "fox".into()]);
+        assert_eq!(tokens, vec!["quick", "fox"]);
+    }
+
+    #[test]
+    fn test_porter_stemmer_collapses_variants() {
+        assert_eq!(PorterStemmer::stem("jumps"), "jump");
+        assert_eq!(PorterStemmer::stem("jumping"), "jump");
+        assert_eq!(PorterStemmer::stem("jumped"), "jump");
+    }
+
+    #[test]
+    fn test_porter_stemmer_keeps_short_words_intact() {
+        assert_eq!(PorterStemmer::stem("is"), "is");
+        assert_eq!(PorterStemmer::stem("as"), "as");
+    }
+
+    #[test]
+    fn test_pipeline_runs_filters_in_order() {
+        let pipeline = TokenizerPipeline::new(SimpleTokenizer)
+            .with_filter(StopWordFilter::english())
+            .with_filter(PorterStemmer);
+
+        let tokens = pipeline.tokenize("The fox jumps over the lazy dogs");
+        assert_eq!(tokens, vec!["fox", "jump", "over", "lazy", "dog"]);
+    }
+
+    #[test]
+    fn test_pipeline_default_matches_simple_tokenizer() {
+        let pipeline = TokenizerPipeline::default();
+        assert_eq!(pipeline.tokenize("Hello, world!"), SimpleTokenizer.tokenize("Hello, world!"));
+    }
+
+    #[test]
+    fn test_word_spans_tracks_byte_ranges() {
+        let spans = word_spans("the quick, brown fox");
+        assert_eq!(
+            spans,
+            vec![("the", 0, 3), ("quick", 4, 9), ("brown", 11, 16), ("fox", 17, 20)]
+        );
+    }
+
+    #[test]
+    fn test_word_spans_empty_text() {
+        assert_eq!(word_spans("   "), Vec::<(&str, usize, usize)>::new());
+    }
+}